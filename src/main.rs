@@ -1,7 +1,25 @@
 use std::env;
 use std::error::Error;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Read};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+// files at or above this size are split into chunks and counted across a
+// worker pool instead of streamed through a single `count_buf` call
+const PARALLEL_THRESHOLD_BYTES: u64 = 64 * 1024 * 1024;
+const DEFAULT_BUFFER_SIZE: usize = 1024 * 1024;
+
+// reads WC_BUFFER_SIZE so users can tune the read buffer / chunk size
+// without recompiling, falling back to the default when it's absent or
+// not a valid `usize`
+fn buffer_size() -> usize {
+    env::var("WC_BUFFER_SIZE")
+        .ok()
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|&size| size > 0)
+        .unwrap_or(DEFAULT_BUFFER_SIZE)
+}
 
 #[derive(Ord, PartialOrd, PartialEq, Eq)]
 enum Mode {
@@ -21,6 +39,33 @@ impl Mode {
             _ => None,
         }
     }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Mode::Lines => "lines",
+            Mode::Words => "words",
+            Mode::Chars => "chars",
+            Mode::Bytes => "bytes",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum OutputFormat {
+    Text,
+    Csv,
+    Json,
+}
+
+impl OutputFormat {
+    fn from_arg(arg: &str) -> Option<Self> {
+        match arg.strip_prefix("--format=")? {
+            "text" => Some(OutputFormat::Text),
+            "csv" => Some(OutputFormat::Csv),
+            "json" => Some(OutputFormat::Json),
+            _ => None,
+        }
+    }
 }
 
 struct BufferDetails {
@@ -35,6 +80,10 @@ struct CountResult {
     words: u64,
     chars: u64,
     bytes: u64,
+    // only meaningful for partial results produced by `scan_chunk`, so that
+    // `merge_chunk_results` can tell whether a word straddles a chunk boundary
+    starts_with_nonspace: bool,
+    ends_with_nonspace: bool,
 }
 
 impl CountResult {
@@ -48,33 +97,176 @@ impl CountResult {
     }
 }
 
+// folds `bytes` into `count`, carrying the in-word/not-in-word state across
+// calls so a caller can drive this over successive buffer refills
+fn scan_bytes(bytes: &[u8], count: &mut CountResult, in_word: &mut bool) {
+    count.bytes += bytes.len() as u64;
+    count.lines += bytes.iter().filter(|&&b| b == b'\n').count() as u64;
+    // every non-continuation byte is the start of a scalar value, so this
+    // counts chars correctly without validating or allocating a String
+    count.chars += bytes.iter().filter(|&&b| (b & 0xC0) != 0x80).count() as u64;
+
+    for &b in bytes {
+        let is_whitespace = b.is_ascii_whitespace();
+        if !is_whitespace && !*in_word {
+            count.words += 1;
+        }
+        *in_word = !is_whitespace;
+    }
+}
+
 fn count_buf(buffer_details: BufferDetails) -> CountResult {
     let mut buffer = buffer_details.buffer;
-    let mut line_buf = Vec::<u8>::new();
     let mut count = CountResult {
         summary: buffer_details.filename,
         ..Default::default()
     };
-    while buffer
-        .read_until(b'\n', &mut line_buf)
-        .expect("read_until failed")
-        != 0
-    {
-        count.lines += 1;
-        count.bytes += line_buf.len() as u64;
-
-        // this moves the ownership of the read data to s
-        // there is no allocation
-        let s = String::from_utf8(line_buf).expect("from_utf8 failed");
-
-        count.words += s.split_whitespace().count() as u64;
-        count.chars += s.chars().count() as u64;
-
-        // this returns the ownership of the read data to buf
-        // there is no allocation
-        line_buf = s.into_bytes();
-        line_buf.clear();
+    let mut in_word = false;
+
+    loop {
+        let available = buffer.fill_buf().expect("fill_buf failed");
+        let len = available.len();
+        if len == 0 {
+            break;
+        }
+
+        scan_bytes(available, &mut count, &mut in_word);
+        buffer.consume(len);
+    }
+
+    count
+}
+
+// counts a single chunk in isolation, recording whether it opens/closes on a
+// non-whitespace byte so adjacent chunks can be stitched back together by
+// `merge_chunk_results` without double-counting a word split across the cut
+fn scan_chunk(chunk: &[u8]) -> CountResult {
+    let mut count = CountResult::default();
+    let mut in_word = false;
+
+    count.starts_with_nonspace = chunk.first().is_some_and(|b| !b.is_ascii_whitespace());
+    scan_bytes(chunk, &mut count, &mut in_word);
+    count.ends_with_nonspace = in_word;
+
+    count
+}
+
+// re-assembles chunk-local partial results, produced in order, into one
+// total. lines/words/bytes/chars all sum directly since `scan_chunk` counts
+// every byte exactly once; the single exception is a word that straddles a
+// chunk boundary, which both neighbouring chunks counted, so it is corrected
+// for here
+fn merge_chunk_results(chunks: Vec<CountResult>) -> CountResult {
+    let mut total = CountResult::default();
+    let mut prev_ends_with_nonspace = false;
+
+    for (i, chunk) in chunks.into_iter().enumerate() {
+        total.lines += chunk.lines;
+        total.bytes += chunk.bytes;
+        total.chars += chunk.chars;
+        total.words += chunk.words;
+
+        if i > 0 && prev_ends_with_nonspace && chunk.starts_with_nonspace {
+            total.words -= 1;
+        }
+
+        prev_ends_with_nonspace = chunk.ends_with_nonspace;
+    }
+
+    total
+}
+
+// upper bound on how many chunks we'll preallocate room for from a size
+// hint, so a corrupt or adversarial metadata length can't trigger an
+// enormous up-front allocation
+const MAX_PREALLOCATED_CHUNKS: usize = 1 << 20;
+
+// splits `file` into fixed-size chunks on a dedicated reader thread, feeds
+// them to a worker pool over a bounded channel, and merges the resulting
+// partial counts back together in order. used once a file crosses
+// `PARALLEL_THRESHOLD_BYTES`, where the thread/channel overhead pays for
+// itself. `size_hint`, when known, sizes the partial-result buffer up front
+// so it isn't repeatedly reallocated while chunks stream in.
+fn count_file_parallel(
+    file: File,
+    filename: Option<String>,
+    chunk_size: usize,
+    size_hint: Option<u64>,
+) -> CountResult {
+    let num_workers = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+
+    let expected_chunks = size_hint
+        .map(|size| (size as usize).div_ceil(chunk_size.max(1)))
+        .unwrap_or(0)
+        .min(MAX_PREALLOCATED_CHUNKS);
+
+    let (chunk_tx, chunk_rx) = mpsc::sync_channel::<(usize, Vec<u8>)>(num_workers * 2);
+    let chunk_rx = Arc::new(Mutex::new(chunk_rx));
+    let (result_tx, result_rx) = mpsc::channel::<(usize, CountResult)>();
+
+    let reader_handle = thread::spawn(move || {
+        let mut reader = BufReader::with_capacity(chunk_size, file);
+        let mut index = 0;
+        loop {
+            let mut chunk = vec![0u8; chunk_size];
+            let mut filled = 0;
+            while filled < chunk.len() {
+                let read = reader.read(&mut chunk[filled..]).expect("read failed");
+                if read == 0 {
+                    break;
+                }
+                filled += read;
+            }
+            if filled == 0 {
+                break;
+            }
+            chunk.truncate(filled);
+            let is_last = filled < chunk_size;
+            if chunk_tx.send((index, chunk)).is_err() {
+                break;
+            }
+            index += 1;
+            if is_last {
+                break;
+            }
+        }
+    });
+
+    let workers: Vec<_> = (0..num_workers)
+        .map(|_| {
+            let chunk_rx = Arc::clone(&chunk_rx);
+            let result_tx = result_tx.clone();
+            thread::spawn(move || loop {
+                let next = chunk_rx.lock().expect("chunk channel poisoned").recv();
+                match next {
+                    Ok((index, chunk)) => {
+                        if result_tx.send((index, scan_chunk(&chunk))).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            })
+        })
+        .collect();
+
+    // drop our own sender so the channel closes once every worker's clone
+    // has also been dropped, letting `result_rx.iter()` terminate below
+    drop(result_tx);
+
+    reader_handle.join().expect("reader thread panicked");
+    for worker in workers {
+        worker.join().expect("worker thread panicked");
     }
+
+    let mut partials: Vec<(usize, CountResult)> = Vec::with_capacity(expected_chunks);
+    partials.extend(result_rx.iter());
+    partials.sort_by_key(|(index, _)| *index);
+
+    let mut count = merge_chunk_results(partials.into_iter().map(|(_, partial)| partial).collect());
+    count.summary = filename;
     count
 }
 
@@ -92,8 +284,10 @@ fn sum_results(results: &Vec<CountResult>) -> CountResult {
     total
 }
 
-fn format_summary(mut results: Vec<CountResult>, mut modes: Vec<Mode>) -> String {
-    // prepare modes for summary formatting
+// sorts/dedups the requested modes and, when summarising more than one
+// input, appends the "Total" row — shared by every output format so they
+// stay consistent with one another
+fn prepare_summary(mut results: Vec<CountResult>, mut modes: Vec<Mode>) -> (Vec<CountResult>, Vec<Mode>) {
     modes.sort();
     modes.dedup();
 
@@ -102,7 +296,11 @@ fn format_summary(mut results: Vec<CountResult>, mut modes: Vec<Mode>) -> String
         results.push(total);
     }
 
-    let output_counts: Vec<Vec<u64>> = results
+    (results, modes)
+}
+
+fn extract_counts(results: &[CountResult], modes: &[Mode]) -> Vec<Vec<u64>> {
+    results
         .iter()
         .map(|result| {
             modes
@@ -110,8 +308,82 @@ fn format_summary(mut results: Vec<CountResult>, mut modes: Vec<Mode>) -> String
                 .map(|mode| result.result_from_mode(mode))
                 .collect()
         })
+        .collect()
+}
+
+// quotes a CSV field per RFC 4180 when it contains a comma, quote, or newline
+fn csv_quote(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// minimal JSON string escaping, just enough for filenames
+fn json_quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn format_csv(results: Vec<CountResult>, modes: Vec<Mode>) -> String {
+    let (results, modes) = prepare_summary(results, modes);
+    let output_counts = extract_counts(&results, &modes);
+
+    let mut header: Vec<&str> = modes.iter().map(|mode| mode.as_str()).collect();
+    header.push("file");
+
+    let mut rows = vec![header.join(",")];
+    for (counts, result) in output_counts.iter().zip(&results) {
+        let mut fields: Vec<String> = counts.iter().map(|count| count.to_string()).collect();
+        fields.push(csv_quote(result.summary.as_deref().unwrap_or("")));
+        rows.push(fields.join(","));
+    }
+
+    rows.join("\n")
+}
+
+fn format_json(results: Vec<CountResult>, modes: Vec<Mode>) -> String {
+    let (results, modes) = prepare_summary(results, modes);
+    let output_counts = extract_counts(&results, &modes);
+
+    let records: Vec<String> = output_counts
+        .iter()
+        .zip(&results)
+        .map(|(counts, result)| {
+            let mut fields: Vec<String> = modes
+                .iter()
+                .zip(counts)
+                .map(|(mode, count)| format!("\"{}\":{}", mode.as_str(), count))
+                .collect();
+            fields.push(format!(
+                "\"file\":{}",
+                json_quote(result.summary.as_deref().unwrap_or(""))
+            ));
+            format!("{{{}}}", fields.join(","))
+        })
         .collect();
 
+    format!("[{}]", records.join(","))
+}
+
+fn format_summary(results: Vec<CountResult>, modes: Vec<Mode>) -> String {
+    let (results, modes) = prepare_summary(results, modes);
+    let output_counts = extract_counts(&results, &modes);
+
     let max_size = output_counts
         .iter()
         .flatten()
@@ -151,8 +423,14 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     let mut modes: Vec<Mode> = vec![];
     let mut filenames: Vec<String> = vec![]; // if no filename is found then read from stdin
+    let mut format = OutputFormat::Text;
 
     for arg in args {
+        if let Some(parsed_format) = OutputFormat::from_arg(&arg) {
+            format = parsed_format;
+            continue;
+        }
+
         let mode = Mode::from_str(&arg);
         match mode {
             Some(mode) => modes.push(mode),
@@ -165,29 +443,54 @@ fn main() -> Result<(), Box<dyn Error>> {
         modes = vec![Mode::Lines, Mode::Words, Mode::Bytes];
     }
 
-    let mut buffers: Vec<BufferDetails> = vec![];
+    // keeps each input's result in argument order, whether it ended up
+    // streamed through `count_buf` or split across the parallel chunk path
+    enum PendingCount {
+        Buffered(BufferDetails),
+        Counted(CountResult),
+    }
+
+    let buffer_size = buffer_size();
+    let mut pending: Vec<PendingCount> = vec![];
 
     if filenames.len() == 0 {
-        buffers.push(BufferDetails {
+        pending.push(PendingCount::Buffered(BufferDetails {
             filename: None,
             buffer: Box::new(std::io::stdin().lock()),
-        })
+        }))
     } else {
         for filename in filenames {
             let file = File::open(&filename)?;
-            buffers.push(BufferDetails {
-                filename: Some(filename),
-                buffer: Box::new(BufReader::new(file)),
-            })
+            let size = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+            if size >= PARALLEL_THRESHOLD_BYTES {
+                pending.push(PendingCount::Counted(count_file_parallel(
+                    file,
+                    Some(filename),
+                    buffer_size,
+                    Some(size),
+                )));
+            } else {
+                pending.push(PendingCount::Buffered(BufferDetails {
+                    filename: Some(filename),
+                    buffer: Box::new(BufReader::with_capacity(buffer_size, file)),
+                }))
+            }
         }
     };
 
-    let results: Vec<_> = buffers
+    let results: Vec<_> = pending
         .into_iter()
-        .map(|buffer| count_buf(buffer))
+        .map(|pending| match pending {
+            PendingCount::Buffered(buffer) => count_buf(buffer),
+            PendingCount::Counted(count) => count,
+        })
         .collect();
 
-    let summary = format_summary(results, modes);
+    let summary = match format {
+        OutputFormat::Text => format_summary(results, modes),
+        OutputFormat::Csv => format_csv(results, modes),
+        OutputFormat::Json => format_json(results, modes),
+    };
 
     println!("{}", summary);
 